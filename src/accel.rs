@@ -0,0 +1,218 @@
+// Hardware-accelerated single-block SHA1 compression, dispatched
+// at runtime to a CPU-specific intrinsic routine when available,
+// falling back to the portable scalar `block` otherwise.
+//
+// Modeled on Intel's "SHA Extensions" sample code and the
+// equivalent ARMv8 Cryptographic Extension routines.
+use std::num::Wrapping;
+
+#[cfg(target_arch = "x86_64")]
+pub(crate) use x86_64::compress;
+
+#[cfg(target_arch = "aarch64")]
+pub(crate) use aarch64::compress;
+
+// No accelerated path on this architecture; always fall back to
+// the scalar implementation in `block`.
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+pub(crate) fn compress(_state: &mut [Wrapping<u32>; 5], _blk: &[Wrapping<u32>; 16]) -> bool {
+    false
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86_64 {
+    use super::Wrapping;
+    use std::arch::x86_64::*;
+    use std::sync::OnceLock;
+
+    // Runs `block` on the CPU's SHA-NI instructions if present.
+    // Returns whether the accelerated path ran; the caller falls
+    // back to the scalar `block` when it returns false.
+    pub(crate) fn compress(state: &mut [Wrapping<u32>; 5], blk: &[Wrapping<u32>; 16]) -> bool {
+        static SUPPORTED: OnceLock<bool> = OnceLock::new();
+        let supported = *SUPPORTED.get_or_init(|| is_x86_feature_detected!("sha"));
+
+        if supported {
+            unsafe { compress_sha_ni(state, blk) };
+        }
+
+        supported
+    }
+
+    // Processes one 512 bit block with the SHA-NI instructions:
+    // SHA1MSG1/SHA1MSG2 roll the 16 word schedule out to 80 words
+    // four at a time, SHA1NEXTE folds `e` into the next message
+    // word, and SHA1RNDS4 runs four rounds at once, with its
+    // immediate (0..=3) selecting the Ch/Parity/Maj/Parity
+    // function and constant band `f`/`sha1_const` encode.
+    #[target_feature(enable = "sha,sse2,ssse3,sse4.1")]
+    unsafe fn compress_sha_ni(state: &mut [Wrapping<u32>; 5], blk: &[Wrapping<u32>; 16]) {
+        let w: [u32; 16] = std::array::from_fn(|i| blk[i].0);
+
+        // `abcd` packs the working variables with `a` in the
+        // high lane and `d` in the low lane (the layout
+        // SHA1RNDS4/SHA1NEXTE expect); `e` is tracked separately
+        // in the high lane of its own register.
+        let mut abcd = _mm_set_epi32(state[0].0 as i32, state[1].0 as i32,
+                                      state[2].0 as i32, state[3].0 as i32);
+        let mut e0 = _mm_set_epi32(state[4].0 as i32, 0, 0, 0);
+        let mut e1;
+
+        let mut msg = [
+            load_msg(&w, 0),
+            load_msg(&w, 4),
+            load_msg(&w, 8),
+            load_msg(&w, 12),
+        ];
+
+        let abcd_save = abcd;
+        let e0_save = e0;
+
+        e0 = _mm_add_epi32(e0, msg[0]);
+        e1 = abcd;
+        abcd = _mm_sha1rnds4_epu32(abcd, e0, 0);
+
+        for g in 1..20 {
+            let idx = g % 4;
+            let band = (g / 5) as i32;
+
+            if idx % 2 == 1 {
+                e1 = _mm_sha1nexte_epu32(e1, msg[idx]);
+                e0 = abcd;
+                abcd = sha1rnds4(abcd, e1, band);
+            } else {
+                e0 = _mm_sha1nexte_epu32(e0, msg[idx]);
+                e1 = abcd;
+                abcd = sha1rnds4(abcd, e0, band);
+            }
+
+            // Extend the schedule, three groups behind the
+            // one just consumed: SHA1MSG1 folds in the w[i-16]
+            // term, the xor adds w[i-8], and SHA1MSG2 finishes
+            // the rotate and folds in w[i-3]. The latter two are
+            // only valid once the register they target has itself
+            // already been consumed (so it isn't still holding
+            // data waiting for its own turn); skip them otherwise.
+            msg[(idx + 3) % 4] = _mm_sha1msg1_epu32(msg[(idx + 3) % 4], msg[idx]);
+            if g >= 2 {
+                msg[(idx + 2) % 4] = _mm_xor_si128(msg[(idx + 2) % 4], msg[idx]);
+            }
+            if g >= 3 {
+                msg[(idx + 1) % 4] = _mm_sha1msg2_epu32(msg[(idx + 1) % 4], msg[idx]);
+            }
+        }
+
+        abcd = _mm_add_epi32(abcd, abcd_save);
+        e0 = _mm_sha1nexte_epu32(e0, e0_save);
+
+        state[0].0 = _mm_extract_epi32(abcd, 3) as u32;
+        state[1].0 = _mm_extract_epi32(abcd, 2) as u32;
+        state[2].0 = _mm_extract_epi32(abcd, 1) as u32;
+        state[3].0 = _mm_extract_epi32(abcd, 0) as u32;
+        state[4].0 = _mm_extract_epi32(e0, 3) as u32;
+    }
+
+    // `_mm_sha1rnds4_epu32`'s round-band selector must be a
+    // compile time immediate; dispatch the dynamic `band` (0..=3)
+    // to it here.
+    #[inline(always)]
+    unsafe fn sha1rnds4(abcd: __m128i, e: __m128i, band: i32) -> __m128i {
+        match band {
+            0 => _mm_sha1rnds4_epu32(abcd, e, 0),
+            1 => _mm_sha1rnds4_epu32(abcd, e, 1),
+            2 => _mm_sha1rnds4_epu32(abcd, e, 2),
+            _ => _mm_sha1rnds4_epu32(abcd, e, 3),
+        }
+    }
+
+    #[inline(always)]
+    unsafe fn load_msg(w: &[u32; 16], at: usize) -> __m128i {
+        _mm_set_epi32(w[at] as i32, w[at + 1] as i32, w[at + 2] as i32, w[at + 3] as i32)
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+mod aarch64 {
+    use super::Wrapping;
+    use std::arch::aarch64::*;
+    use std::sync::OnceLock;
+
+    const AT_HWCAP: std::os::raw::c_ulong = 16;
+    const HWCAP_SHA1: std::os::raw::c_ulong = 1 << 5;
+
+    extern "C" {
+        fn getauxval(kind: std::os::raw::c_ulong) -> std::os::raw::c_ulong;
+    }
+
+    pub(crate) fn compress(state: &mut [Wrapping<u32>; 5], blk: &[Wrapping<u32>; 16]) -> bool {
+        static SUPPORTED: OnceLock<bool> = OnceLock::new();
+        let supported = *SUPPORTED
+            .get_or_init(|| unsafe { getauxval(AT_HWCAP) } & HWCAP_SHA1 != 0);
+
+        if supported {
+            unsafe { compress_sha1(state, blk) };
+        }
+
+        supported
+    }
+
+    // Processes one 512 bit block with the ARMv8 Cryptographic
+    // Extension: SHA1SU0/SHA1SU1 extend the schedule, and
+    // SHA1C/SHA1P/SHA1M run four rounds each for the Ch/Parity/Maj
+    // function bands `f`/`sha1_const` encode.
+    #[target_feature(enable = "sha2")]
+    unsafe fn compress_sha1(state: &mut [Wrapping<u32>; 5], blk: &[Wrapping<u32>; 16]) {
+        const K: [u32; 4] = [0x5a827999, 0x6ed9eba1, 0x8f1bbcdc, 0xca62c1d6];
+
+        let w: [u32; 16] = std::array::from_fn(|i| blk[i].0);
+        let abcd_in = [state[0].0, state[1].0, state[2].0, state[3].0];
+
+        let mut abcd = vld1q_u32(abcd_in.as_ptr());
+        let mut e0 = state[4].0;
+
+        let abcd_save = abcd;
+        let e0_save = e0;
+
+        let mut msgs = [
+            vld1q_u32(w[0..4].as_ptr()),
+            vld1q_u32(w[4..8].as_ptr()),
+            vld1q_u32(w[8..12].as_ptr()),
+            vld1q_u32(w[12..16].as_ptr()),
+        ];
+        for g in 0..20 {
+            let idx = g % 4;
+            let band = g / 5;
+            let k = vdupq_n_u32(K[band]);
+            let tmp = vaddq_u32(msgs[idx], k);
+
+            let e1 = vsha1h_u32(vgetq_lane_u32(abcd, 0));
+            abcd = match band {
+                0 => vsha1cq_u32(abcd, e0, tmp),
+                1 | 3 => vsha1pq_u32(abcd, e0, tmp),
+                _ => vsha1mq_u32(abcd, e0, tmp),
+            };
+            e0 = e1;
+
+            // Replace the group just consumed with the one four
+            // groups ahead, built from it and the three after it.
+            if g + 4 < 20 {
+                let next1 = (idx + 1) % 4;
+                let next2 = (idx + 2) % 4;
+                let next3 = (idx + 3) % 4;
+                msgs[idx] = vsha1su0q_u32(msgs[idx], msgs[next1], msgs[next2]);
+                msgs[idx] = vsha1su1q_u32(msgs[idx], msgs[next3]);
+            }
+        }
+
+        abcd = vaddq_u32(abcd, abcd_save);
+        e0 = e0.wrapping_add(e0_save);
+
+        let mut out = [0u32; 4];
+        vst1q_u32(out.as_mut_ptr(), abcd);
+        state[0].0 = out[0];
+        state[1].0 = out[1];
+        state[2].0 = out[2];
+        state[3].0 = out[3];
+        state[4].0 = e0;
+    }
+}
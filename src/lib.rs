@@ -1,21 +1,25 @@
 // Implemented pursuant to NIST FIPS 180-4
 // available at https://doi.org/10.6028/NIST.FIPS.180-4
-use {
-    std::{
-        num::Wrapping,
-        cmp::max,
-    }
-};
+use std::num::Wrapping;
 
-// Unit tests for this implementation of the 
+// Unit tests for this implementation of the
 // SHA1 function
 #[cfg(test)]
 mod tests;
 
+// CPU-specific accelerated block compression, used when the
+// running hardware supports it.
+mod accel;
+
+// SHA-256, sharing the block-packing and padding machinery
+// defined below with SHA-1.
+mod sha256;
+pub use sha256::{Sha256, Sha256Context};
+
 #[derive(Debug)]
 pub enum HashError {
-    // Data to be hashed by SHA1 greater
-    // than or equal to 2^64 bits in length
+    // Data to be hashed greater than or equal to
+    // 2^64 bits in length
     DataTooLarge
 }
 
@@ -28,13 +32,36 @@ pub struct Sha1 {
 
 impl Sha1 {
     pub fn new(inp: &[u8]) -> Result<Sha1, HashError> {
-        Ok(Sha1 {
-            hash: sha1(inp)?
-        })
+        let mut ctx = Sha1Context::new();
+        ctx.update(inp)?;
+        ctx.finish()
+    }
+
+    // Hashes everything available from 'r' until EOF, without
+    // holding the whole input in memory at once.
+    pub fn from_reader<R: std::io::Read>(r: &mut R) -> std::io::Result<Sha1> {
+        let mut ctx = Sha1Context::new();
+        let mut buf = [0_u8; 8192];
+
+        loop {
+            let read = r.read(&mut buf)?;
+            if read == 0 {
+                break;
+            }
+
+            ctx.update(&buf[..read])
+                .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidInput, "data too large"))?;
+        }
+
+        ctx.finish()
+            .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidInput, "data too large"))
     }
 
+    // Mirrors 'Sha256::to_string'; kept inherent, rather than behind
+    // 'Display', for symmetry with that API.
+    #[allow(clippy::inherent_to_string)]
     pub fn to_string(&self) -> String {
-        format!("{:08x}{:08x}{:08x}{:08x}{:08x}", 
+        format!("{:08x}{:08x}{:08x}{:08x}{:08x}",
                 self.hash[0],
                 self.hash[1],
                 self.hash[2],
@@ -43,36 +70,248 @@ impl Sha1 {
     }
 }
 
-// SHA1 function takes in a u8 slice that is
-// less than 2^64 bits in length and returns
-// a 160 bit hash composed of u32 bit parts
-fn sha1(inp: &[u8]) -> Result<[u32; 5], HashError> {
-    if inp.len() >= 2 << 61 {
-        return Err(HashError::DataTooLarge);
+// Incremental SHA1 digest, for hashing data that arrives in
+// pieces (files, sockets, ...) without holding the whole
+// message in memory at once.
+//
+// Bytes are fed in through repeated calls to 'update', and
+// the digest is produced by a final call to 'finish'. This
+// mirrors the multi-step digest APIs used by other hashing
+// libraries (e.g. ring's 'Context').
+pub struct Sha1Context {
+    state: [Wrapping<u32>; 5],
+    buf: BlockBuffer,
+}
+
+impl Sha1Context {
+    pub fn new() -> Sha1Context {
+        Sha1Context {
+            state: [
+                Wrapping(0x67452301),
+                Wrapping(0xefcdab89),
+                Wrapping(0x98badcfe),
+                Wrapping(0x10325476),
+                Wrapping(0xc3d2e1f0),
+            ],
+            buf: BlockBuffer::new(),
+        }
+    }
+
+    // Feed more data into the running hash. May be called
+    // any number of times before 'finish'.
+    pub fn update(&mut self, data: &[u8]) -> Result<(), HashError> {
+        let state = &mut self.state;
+        self.buf.update(data, |blk| block_dispatch(state, blk))
+    }
+
+    // Apply the final padding, process the last one or two
+    // blocks, and return the resulting digest. Consumes the
+    // context, since a SHA1 context cannot be fed more data
+    // once it has been finalized.
+    pub fn finish(mut self) -> Result<Sha1, HashError> {
+        let (tail, used) = self.buf.pad_tail();
+
+        for chunk in tail[..used].chunks_exact(64) {
+            let mut block_bytes = [0_u8; 64];
+            block_bytes.copy_from_slice(chunk);
+            block_dispatch(&mut self.state, &pack_block(&block_bytes));
+        }
+
+        Ok(Sha1 {
+            hash: [
+                self.state[0].0,
+                self.state[1].0,
+                self.state[2].0,
+                self.state[3].0,
+                self.state[4].0,
+            ],
+        })
+    }
+}
+
+impl Default for Sha1Context {
+    fn default() -> Sha1Context {
+        Sha1Context::new()
+    }
+}
+
+// Adapts a 'Sha1Context' into a 'std::io::Write' sink, so it can sit
+// at the end of an 'std::io::copy' (or anything else that writes into
+// a 'Write') instead of requiring the caller to buffer chunks and call
+// 'update' directly. Call 'finish' once writing is done.
+pub struct Sha1Writer {
+    ctx: Sha1Context,
+}
+
+impl Sha1Writer {
+    pub fn new() -> Sha1Writer {
+        Sha1Writer { ctx: Sha1Context::new() }
+    }
+
+    pub fn finish(self) -> Result<Sha1, HashError> {
+        self.ctx.finish()
+    }
+}
+
+impl Default for Sha1Writer {
+    fn default() -> Sha1Writer {
+        Sha1Writer::new()
+    }
+}
+
+impl std::io::Write for Sha1Writer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.ctx.update(buf)
+            .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidInput, "data too large"))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
     }
+}
 
-    // Initial hash values to be used.
-    let mut hash: [Wrapping<u32>; 5] = [
-        Wrapping(0x67452301),
-        Wrapping(0xefcdab89),
-        Wrapping(0x98badcfe),
-        Wrapping(0x10325476),
-        Wrapping(0xc3d2e1f0),
+// Runs a single 512 bit SHA1 compression round over a
+// caller-supplied state and block, with no padding applied.
+//
+// This is the reusable primitive underneath 'Sha1Context': protocols
+// that need their own message framing (length-extension tooling,
+// custom padding, other Merkle-Damgard constructions) can drive the
+// round function directly instead of going through 'Sha1'/'Sha1Context'.
+pub fn sha1_compress(state: &mut [u32; 5], block: &[u8; 64]) {
+    let mut wrapped = [
+        Wrapping(state[0]),
+        Wrapping(state[1]),
+        Wrapping(state[2]),
+        Wrapping(state[3]),
+        Wrapping(state[4]),
     ];
 
-    let blocks = pad_data(inp);
+    block_dispatch(&mut wrapped, &pack_block(block));
+
+    *state = [
+        wrapped[0].0,
+        wrapped[1].0,
+        wrapped[2].0,
+        wrapped[3].0,
+        wrapped[4].0,
+    ];
+}
 
-    for i in 0..blocks.len() {
-        block(&mut hash, &blocks[i]);
+// Packs a 64 byte block into sixteen big endian u32 words,
+// the layout the SHA1 and SHA256 block functions both operate on.
+fn pack_block(bytes: &[u8; 64]) -> [Wrapping<u32>; 16] {
+    let mut words = [Wrapping(0_u32); 16];
+    for (word, chunk) in words.iter_mut().zip(bytes.chunks_exact(4)) {
+        *word = Wrapping(u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]));
     }
+    words
+}
 
-    Ok([
-       hash[0].0,
-       hash[1].0,
-       hash[2].0,
-       hash[3].0,
-       hash[4].0,
-    ])
+// Buffers input between calls to a context's 'update', packing
+// complete 512-bit blocks as they fill and tracking the total bit
+// length for the final padding. Shared by 'Sha1Context' and
+// 'Sha256Context' so the two don't drift on their block/length
+// bookkeeping; only the block function itself differs between
+// the two algorithms.
+struct BlockBuffer {
+    // Bytes accumulated since the last full block was
+    // processed. Only the first 'buffered' bytes are valid.
+    buffer: [u8; 64],
+    buffered: usize,
+    // Total number of input bits seen so far, across every
+    // call to 'update'.
+    total_bits: u64,
+}
+
+impl BlockBuffer {
+    fn new() -> BlockBuffer {
+        BlockBuffer {
+            buffer: [0_u8; 64],
+            buffered: 0,
+            total_bits: 0,
+        }
+    }
+
+    // Feeds 'data' in, calling 'on_block' with each complete
+    // 512-bit block packed along the way. Returns an error once
+    // the running total would reach or exceed 2^64 bits, the
+    // exact limit the 64 bit length field in the padding can
+    // represent.
+    fn update(&mut self, data: &[u8], mut on_block: impl FnMut(&[Wrapping<u32>; 16])) -> Result<(), HashError> {
+        let bit_len = (data.len() as u64).checked_mul(8)
+            .and_then(|bits| self.total_bits.checked_add(bits))
+            .ok_or(HashError::DataTooLarge)?;
+        self.total_bits = bit_len;
+
+        let mut data = data;
+
+        // Top up a partial block left over from a previous
+        // call before looking at any fresh whole blocks.
+        if self.buffered > 0 {
+            let need = 64 - self.buffered;
+            let take = need.min(data.len());
+            self.buffer[self.buffered..self.buffered + take]
+                .copy_from_slice(&data[..take]);
+            self.buffered += take;
+            data = &data[take..];
+
+            if self.buffered < 64 {
+                return Ok(());
+            }
+
+            on_block(&pack_block(&self.buffer));
+            self.buffered = 0;
+        }
+
+        // Feed complete 512-bit blocks straight from the
+        // input, keeping at most 63 trailing bytes buffered.
+        while data.len() >= 64 {
+            let mut chunk = [0_u8; 64];
+            chunk.copy_from_slice(&data[..64]);
+            on_block(&pack_block(&chunk));
+            data = &data[64..];
+        }
+
+        self.buffer[..data.len()].copy_from_slice(data);
+        self.buffered = data.len();
+
+        Ok(())
+    }
+
+    fn pad_tail(&self) -> ([u8; 128], usize) {
+        pad_tail(&self.buffer, self.buffered, self.total_bits)
+    }
+}
+
+// Appends the Merkle-Damgard padding (a 0x80 byte, zero fill, then
+// the 64 bit big-endian bit length) after whatever is left over in
+// a context's carry buffer, returning the one or two 64-byte blocks
+// that need to be compressed to finish the digest.
+//
+// SHA1 and SHA256 pad identically; this is shared by both contexts'
+// 'finish', which differ only in which block function they run.
+fn pad_tail(buffer: &[u8; 64], buffered: usize, total_bits: u64) -> ([u8; 128], usize) {
+    let mut tail = [0_u8; 128];
+    tail[..buffered].copy_from_slice(&buffer[..buffered]);
+    tail[buffered] = 0x80;
+
+    // If there isn't room left in this block for the
+    // 64 bit length, pad it out and spill into a second
+    // block.
+    let used = if buffered + 1 > 56 { 128 } else { 64 };
+    tail[used - 8..used].copy_from_slice(&total_bits.to_be_bytes());
+
+    (tail, used)
+}
+
+// Compresses one block, using the accelerated path for the
+// running CPU when one is available and falling back to the
+// portable scalar 'block' otherwise.
+fn block_dispatch(hash: &mut [Wrapping<u32>; 5], blk: &[Wrapping<u32>; 16]) {
+    if !accel::compress(hash, blk) {
+        block(hash, blk);
+    }
 }
 
 fn block(hash: &mut [Wrapping<u32>; 5], block: &[Wrapping<u32>; 16]) {
@@ -123,64 +362,50 @@ fn block(hash: &mut [Wrapping<u32>; 5], block: &[Wrapping<u32>; 16]) {
     hash[4] += e;
 }
 
-// Converts u8 slice into a vector of 512 bit 
-// blocks, represented as u32 arrays with 
+// Converts u8 slice into a vector of 512 bit
+// blocks, represented as u32 arrays with
 // length 16
+//
+// Only exercised directly by tests for now; the streaming
+// Sha1Context path above has its own (simpler) buffering, and
+// no other one-shot caller needs this yet.
+#[cfg(test)]
 fn pad_data(inp: &[u8]) -> Vec<[Wrapping<u32>; 16]> {
-    let inp_len_bits = inp.len() * 8;
-                     // Divide size of input data by
-                     // size of a single block
-                     //
-                     // max function used because if length of
-                     // input is zero, then there still needs to be
-                     // one block
-    let num_blocks = max(((inp_len_bits as f32 / 512_f32).ceil()
-                     // If there is not enough space
-                     // left in a block to insert a '1' bit
-                     // and the 64 bit number representing
-                     // the size of the input data,
-                     // then add one more block.
-                   + ((inp_len_bits % 512) as f32 / 448_f32).floor()) as usize, 1);
-
-    let mut blocks = vec![[Wrapping(0_u32); 16]; num_blocks];
+    let total_bits = (inp.len() as u64).checked_mul(8)
+        .expect("input too large to hash");
 
-    let mut block_num = 0;
-    let mut block_pos = 0;
+    // Enough 64 byte blocks to hold the input, the 0x80 byte, and
+    // the 8 byte length, rounded up; the zero length case still
+    // needs one block for the 0x80 byte and the length alone.
+    let num_blocks = (inp.len() + 1 + 8).div_ceil(64);
 
-    for (i, x) in inp.iter().enumerate() {
-        // Current block
-        block_num = (i as f32 / 64_f32).floor() as usize;
-        // Current position in block
-        block_pos = ((i % 64) as f32 / 4_f32).floor() as usize;
-
-        // Big Endian implementation, fill up empty u32 elements
-        // by ORing it with four u8 elements starting from
-        // left to right.
-        blocks[block_num][block_pos].0 |= (*x as u32) << 24 - (i % 4 * 8);
-    }
-
-    // Which u32 segment the '1' bit should occupy after
-    // the last u8 input byte
-    let next_input = inp.len() % 4;
+    let mut blocks = vec![[Wrapping(0_u32); 16]; num_blocks];
 
-    if next_input == 0 && inp.len() > 0 {
-        if block_pos == 15 {
-            block_pos = 0;
-            block_num = 0;
-        } else {
-            block_pos += 1;
-        }
+    // Big Endian implementation: pack four bytes at a time into
+    // each word, left to right. Any chunk shorter than four bytes
+    // is the final, partial word of real input; the rest of that
+    // word, and every word beyond it, stays zero until the 0x80
+    // byte and length are written below.
+    for (word, chunk) in blocks.iter_mut()
+        .flat_map(|block| block.iter_mut())
+        .zip(inp.chunks(4))
+    {
+        let mut bytes = [0_u8; 4];
+        bytes[..chunk.len()].copy_from_slice(chunk);
+        word.0 = u32::from_be_bytes(bytes);
     }
 
-    // Set most significant bit (which is the bit neighboring
-    // the last input byte) to '1'
-    blocks[block_num][block_pos].0 |= 128_u32 << 24 - next_input * 8;
+    // Word immediately after the last input byte, and how many of
+    // its bytes (from the left) are already real input.
+    let next_word = inp.len() / 4;
+    let next_byte = inp.len() % 4;
+    blocks[next_word / 16][next_word % 16].0 |= 128_u32 << (24 - next_byte * 8);
 
     // Fill end of last block with 64 bit number representing
     // size of input data in bits
     let blocks_len = blocks.len();
-    blocks[blocks_len - 1][15].0 = inp_len_bits as u32;
-    blocks[blocks_len - 1][14].0 = (inp_len_bits >> 32) as u32;
+    blocks[blocks_len - 1][15].0 = total_bits as u32;
+    blocks[blocks_len - 1][14].0 = (total_bits >> 32) as u32;
 
     blocks
 }
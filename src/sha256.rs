@@ -0,0 +1,189 @@
+// Implemented pursuant to NIST FIPS 180-4
+// available at https://doi.org/10.6028/NIST.FIPS.180-4
+use std::num::Wrapping;
+
+use crate::{pack_block, BlockBuffer, HashError};
+
+// Encapsulate raw hash in a struct with
+// convenience function to convert hash
+// to hex string
+pub struct Sha256 {
+    hash: [u32; 8],
+}
+
+impl Sha256 {
+    pub fn new(inp: &[u8]) -> Result<Sha256, HashError> {
+        let mut ctx = Sha256Context::new();
+        ctx.update(inp)?;
+        ctx.finish()
+    }
+
+    // Mirrors 'Sha1::to_string'; kept inherent, rather than behind
+    // 'Display', for symmetry with that API.
+    #[allow(clippy::inherent_to_string)]
+    pub fn to_string(&self) -> String {
+        self.hash.iter().map(|word| format!("{:08x}", word)).collect()
+    }
+}
+
+// Incremental SHA256 digest, mirroring 'Sha1Context': bytes are fed
+// in through repeated calls to 'update', and the digest is produced
+// by a final call to 'finish'.
+pub struct Sha256Context {
+    state: [Wrapping<u32>; 8],
+    buf: BlockBuffer,
+}
+
+impl Sha256Context {
+    pub fn new() -> Sha256Context {
+        Sha256Context {
+            state: [
+                Wrapping(0x6a09e667),
+                Wrapping(0xbb67ae85),
+                Wrapping(0x3c6ef372),
+                Wrapping(0xa54ff53a),
+                Wrapping(0x510e527f),
+                Wrapping(0x9b05688c),
+                Wrapping(0x1f83d9ab),
+                Wrapping(0x5be0cd19),
+            ],
+            buf: BlockBuffer::new(),
+        }
+    }
+
+    // Feed more data into the running hash. May be called
+    // any number of times before 'finish'.
+    pub fn update(&mut self, data: &[u8]) -> Result<(), HashError> {
+        let state = &mut self.state;
+        self.buf.update(data, |blk| block(state, blk))
+    }
+
+    // Apply the final padding, process the last one or two
+    // blocks, and return the resulting digest. Consumes the
+    // context, since a SHA256 context cannot be fed more data
+    // once it has been finalized.
+    pub fn finish(mut self) -> Result<Sha256, HashError> {
+        let (tail, used) = self.buf.pad_tail();
+
+        for chunk in tail[..used].chunks_exact(64) {
+            let mut block_bytes = [0_u8; 64];
+            block_bytes.copy_from_slice(chunk);
+            block(&mut self.state, &pack_block(&block_bytes));
+        }
+
+        Ok(Sha256 {
+            hash: [
+                self.state[0].0,
+                self.state[1].0,
+                self.state[2].0,
+                self.state[3].0,
+                self.state[4].0,
+                self.state[5].0,
+                self.state[6].0,
+                self.state[7].0,
+            ],
+        })
+    }
+}
+
+impl Default for Sha256Context {
+    fn default() -> Sha256Context {
+        Sha256Context::new()
+    }
+}
+
+fn block(hash: &mut [Wrapping<u32>; 8], block: &[Wrapping<u32>; 16]) {
+    // Message schedule used for the rounds
+    let mut w = [Wrapping(0_u32); 64];
+
+    let (mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h)
+        = (
+            hash[0],
+            hash[1],
+            hash[2],
+            hash[3],
+            hash[4],
+            hash[5],
+            hash[6],
+            hash[7],
+          );
+
+    for i in 0..64 {
+        if i < 16 {
+            w[i] = block[i];
+        } else {
+            let small_sigma0 = Wrapping(w[i-15].0.rotate_right(7))
+                ^ Wrapping(w[i-15].0.rotate_right(18))
+                ^ Wrapping(w[i-15].0 >> 3);
+            let small_sigma1 = Wrapping(w[i-2].0.rotate_right(17))
+                ^ Wrapping(w[i-2].0.rotate_right(19))
+                ^ Wrapping(w[i-2].0 >> 10);
+            w[i] = w[i-16] + small_sigma0 + w[i-7] + small_sigma1;
+        }
+
+        let big_sigma1 = Wrapping(e.0.rotate_right(6))
+            ^ Wrapping(e.0.rotate_right(11))
+            ^ Wrapping(e.0.rotate_right(25));
+        let t1 = h + big_sigma1 + Wrapping(ch(e.0, f.0, g.0)) + Wrapping(sha256_const(i)) + w[i];
+
+        let big_sigma0 = Wrapping(a.0.rotate_right(2))
+            ^ Wrapping(a.0.rotate_right(13))
+            ^ Wrapping(a.0.rotate_right(22));
+        let t2 = big_sigma0 + Wrapping(maj(a.0, b.0, c.0));
+
+        h = g;
+        g = f;
+        f = e;
+        e = d + t1;
+        d = c;
+        c = b;
+        b = a;
+        a = t1 + t2;
+    }
+
+    hash[0] += a;
+    hash[1] += b;
+    hash[2] += c;
+    hash[3] += d;
+    hash[4] += e;
+    hash[5] += f;
+    hash[6] += g;
+    hash[7] += h;
+}
+
+// Choose function: for each bit, picks from 'y' or 'z'
+// depending on the corresponding bit of 'x'.
+fn ch(x: u32, y: u32, z: u32) -> u32 {
+    (x & y) ^ (!x & z)
+}
+
+// Majority function: for each bit, the value held by at
+// least two of 'x', 'y', 'z'.
+fn maj(x: u32, y: u32, z: u32) -> u32 {
+    (x & y) ^ (x & z) ^ (y & z)
+}
+
+// Per-round constants: the fractional parts of the cube
+// roots of the first 64 primes.
+fn sha256_const(round: usize) -> u32 {
+    const K: [u32; 64] = [
+        0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5,
+        0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+        0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3,
+        0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+        0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc,
+        0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+        0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7,
+        0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+        0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13,
+        0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+        0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3,
+        0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+        0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5,
+        0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+        0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208,
+        0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+    ];
+
+    K[round]
+}